@@ -1,14 +1,167 @@
 use crate::error::Error;
 use crate::grep_score::{match_and_score_grep_items, GrepScoringContext};
 use crate::types::{GrepItem, GrepSearchResult};
-use grep_regex::RegexMatcherBuilder;
-use grep_searcher::sinks::UTF8;
-use grep_searcher::SearcherBuilder;
+use grep_matcher::Matcher;
+use grep_regex::{RegexMatcher, RegexMatcherBuilder};
+use grep_searcher::{SearcherBuilder, Sink, SinkContext, SinkFinish, SinkMatch};
 use ignore::WalkBuilder;
-use std::path::PathBuf;
+use std::collections::VecDeque;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use tracing::{debug, info};
 
+/// A match awaiting the remainder of its after-context before it can be emitted.
+struct PendingMatch {
+    item: GrepItem,
+    after_remaining: usize,
+}
+
+/// A [`Sink`] that reconstructs the before/after context lines around each
+/// matching line and emits a fully-populated [`GrepItem`] — complete with match
+/// spans and surrounding context — through the caller's callback.
+///
+/// Context is reconstructed from absolute line numbers rather than from
+/// `grep-searcher`'s Before/After delivery kinds: a rolling window feeds each
+/// match its before-context and every subsequent line is offered to the pending
+/// matches that still need after-context. This way a line shared between two
+/// nearby matches (closer than `2 * context_lines`) appears in *both* items'
+/// context, instead of landing in only whichever one the delivery kind happened
+/// to favour.
+struct ContextSink<'a, F: Fn(GrepItem)> {
+    matcher: &'a RegexMatcher,
+    path: &'a Path,
+    base_path: &'a Path,
+    emit: &'a F,
+    emitted: &'a AtomicUsize,
+    context_lines: usize,
+    /// The last `context_lines` lines seen, in order, used as before-context.
+    recent: VecDeque<(u64, String)>,
+    /// Matches still collecting after-context, ordered by line number.
+    pending: Vec<PendingMatch>,
+}
+
+impl<'a, F: Fn(GrepItem)> ContextSink<'a, F> {
+    fn new(
+        matcher: &'a RegexMatcher,
+        path: &'a Path,
+        base_path: &'a Path,
+        emit: &'a F,
+        emitted: &'a AtomicUsize,
+        context_lines: usize,
+    ) -> Self {
+        Self {
+            matcher,
+            path,
+            base_path,
+            emit,
+            emitted,
+            context_lines,
+            recent: VecDeque::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    fn relative_path(&self) -> String {
+        pathdiff::diff_paths(self.path, self.base_path)
+            .unwrap_or_else(|| self.path.to_path_buf())
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// Process one line delivered by the searcher, whether it is a match or a
+    /// context line, keyed by its absolute line number.
+    fn record_line(&mut self, line_number: u64, text: String, matched: bool) {
+        // Offer this line as after-context to every pending match that still
+        // needs it (and whose own line precedes this one).
+        for pending in &mut self.pending {
+            if pending.after_remaining > 0 && line_number > pending.item.line_number as u64 {
+                pending.item.context_after.push(text.clone());
+                pending.after_remaining -= 1;
+            }
+        }
+
+        if matched {
+            let mut match_ranges: Vec<(usize, usize)> = Vec::new();
+            let _ = self.matcher.find_iter(text.as_bytes(), |m| {
+                match_ranges.push((m.start(), m.end()));
+                true
+            });
+            let column = match_ranges.first().map_or(0, |&(start, _)| start);
+
+            let context_before = self.recent.iter().map(|(_, t)| t.clone()).collect();
+
+            self.pending.push(PendingMatch {
+                item: GrepItem {
+                    path: self.path.to_path_buf(),
+                    relative_path: self.relative_path(),
+                    line_number: line_number as usize,
+                    line_content: text.clone(),
+                    column,
+                    match_ranges,
+                    context_before,
+                    context_after: Vec::new(),
+                },
+                after_remaining: self.context_lines,
+            });
+        }
+
+        // Advance the rolling before-context window.
+        self.recent.push_back((line_number, text));
+        while self.recent.len() > self.context_lines {
+            self.recent.pop_front();
+        }
+
+        self.flush_ready();
+    }
+
+    /// Emit, in line order, every pending match that has gathered all of its
+    /// after-context. Because matches arrive in increasing line order, the one
+    /// at the front always completes first.
+    fn flush_ready(&mut self) {
+        while self
+            .pending
+            .first()
+            .is_some_and(|p| p.after_remaining == 0)
+        {
+            let pending = self.pending.remove(0);
+            (self.emit)(pending.item);
+            self.emitted.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl<F: Fn(GrepItem)> Sink for ContextSink<'_, F> {
+    type Error = io::Error;
+
+    fn matched(&mut self, _searcher: &grep_searcher::Searcher, mat: &SinkMatch) -> Result<bool, io::Error> {
+        let text = String::from_utf8_lossy(mat.bytes())
+            .trim_end_matches('\n')
+            .to_string();
+        self.record_line(mat.line_number().unwrap_or(0), text, true);
+        Ok(true)
+    }
+
+    fn context(&mut self, _searcher: &grep_searcher::Searcher, ctx: &SinkContext) -> Result<bool, io::Error> {
+        let text = String::from_utf8_lossy(ctx.bytes())
+            .trim_end_matches('\n')
+            .to_string();
+        self.record_line(ctx.line_number().unwrap_or(0), text, false);
+        Ok(true)
+    }
+
+    fn finish(&mut self, _searcher: &grep_searcher::Searcher, _: &SinkFinish) -> Result<(), io::Error> {
+        // Flush any matches whose after-context ran into end-of-file.
+        for pending in self.pending.drain(..) {
+            (self.emit)(pending.item);
+            self.emitted.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+}
+
 pub struct ContentSearcher {
     base_path: PathBuf,
 }
@@ -22,6 +175,20 @@ impl ContentSearcher {
         Ok(Self { base_path })
     }
 
+    /// Resolve the decompressor command for a path based on its extension,
+    /// following ripgrep's default mapping. Returns `None` for extensions we
+    /// don't know how to decompress.
+    fn decompressor_for(path: &Path) -> Option<(&'static str, &'static [&'static str])> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("gz") => Some(("gzip", &["-d", "-c"])),
+            Some("bz2") => Some(("bzip2", &["-d", "-c"])),
+            Some("xz") => Some(("xz", &["-d", "-c"])),
+            Some("zst") | Some("zstd") => Some(("zstd", &["-q", "-d", "-c"])),
+            Some("lz4") => Some(("lz4", &["-d", "-c"])),
+            _ => None,
+        }
+    }
+
     /// Convert a fuzzy query into a permissive regex pattern that allows typos
     /// For example, "funk" becomes "f(u|.)n(k|.)" to match "func", "function", etc.
     fn fuzzy_query_to_regex(query: &str) -> String {
@@ -63,39 +230,59 @@ impl ContentSearcher {
         escaped
     }
 
-    /// Perform grep search in the directory
-    pub fn grep_search(
+    /// Perform grep search in the directory, emitting each [`GrepItem`] through
+    /// `sink` as soon as the file that contains it is searched.
+    ///
+    /// The parallel walk checks `cancel` on every entry and returns
+    /// [`ignore::WalkState::Quit`] when it is set, so a newer query can abort an
+    /// in-flight search without waiting for the whole tree. It also quits once
+    /// `max_results` items have been emitted across all threads.
+    ///
+    /// `context_lines` configures how many lines of surrounding context are
+    /// captured before and after each match (see [`GrepItem::context_before`]
+    /// and [`GrepItem::context_after`]).
+    ///
+    /// When `decompress` is set, files whose extension maps to a known
+    /// decompressor are piped through that binary before searching, so
+    /// compressed logs can be grepped without extracting them first.
+    #[allow(clippy::too_many_arguments)]
+    pub fn grep_search_streaming(
         &self,
         pattern: &str,
         max_results: usize,
         max_threads: usize,
-    ) -> Result<Vec<GrepItem>, Error> {
-        info!("Starting grep search for pattern: {}", pattern);
+        context_lines: usize,
+        decompress: bool,
+        cancel: Arc<AtomicBool>,
+        sink: impl Fn(GrepItem) + Send + Sync,
+    ) -> Result<(), Error> {
+        info!("Starting streaming grep search for pattern: {}", pattern);
 
         let matcher = RegexMatcherBuilder::new()
             .case_insensitive(true)
             .build(pattern)
             .map_err(|e| Error::GrepError(e.to_string()))?;
 
-        let results = Arc::new(Mutex::new(Vec::new()));
-        let max_results = Arc::new(max_results);
+        let emitted = Arc::new(AtomicUsize::new(0));
+        let sink = &sink;
 
         WalkBuilder::new(&self.base_path)
             .threads(max_threads.max(1))
             .build_parallel()
             .run(|| {
                 let matcher = matcher.clone();
-                let results = Arc::clone(&results);
-                let max_results = Arc::clone(&max_results);
+                let emitted = Arc::clone(&emitted);
+                let cancel = Arc::clone(&cancel);
                 let base_path = self.base_path.clone();
 
                 Box::new(move |entry| {
-                    // Check if we've hit the limit
-                    {
-                        let current_results = results.lock().unwrap();
-                        if current_results.len() >= *max_results {
-                            return ignore::WalkState::Quit;
-                        }
+                    // Abort early when a newer query supersedes this one, or once
+                    // we have emitted enough results.
+                    if cancel.load(Ordering::Relaxed) {
+                        return ignore::WalkState::Quit;
+                    }
+                    if emitted.load(Ordering::Relaxed) >= max_results {
+                        return ignore::WalkState::Quit;
                     }
 
                     let entry = match entry {
@@ -113,49 +300,92 @@ impl ContentSearcher {
                     // Search in this file
                     let mut searcher = SearcherBuilder::new()
                         .line_number(true)
+                        .before_context(context_lines)
+                        .after_context(context_lines)
                         .build();
 
-                    let mut file_results = Vec::new();
+                    // When decompression is enabled and the extension maps to a
+                    // known decompressor, pipe the file through that binary and
+                    // search its stdout; if the binary is missing, fall back to
+                    // searching the file as plain text.
+                    let decompressor = if decompress {
+                        Self::decompressor_for(path)
+                    } else {
+                        None
+                    };
 
-                    let search_result = searcher.search_path(
+                    let item_sink = ContextSink::new(
                         &matcher,
                         path,
-                        UTF8(|lnum, line| {
-                            let line_str = line.trim_end_matches('\n').to_string();
-
-                            let relative_path = pathdiff::diff_paths(path, &base_path)
-                                .unwrap_or_else(|| path.to_path_buf())
-                                .to_string_lossy()
-                                .into_owned();
-
-                            file_results.push(GrepItem {
-                                path: path.to_path_buf(),
-                                relative_path: relative_path.clone(),
-                                line_number: lnum as usize,
-                                line_content: line_str,
-                                column: 0, // We'll calculate this later if needed
-                            });
-
-                            Ok(true)
-                        }),
+                        &base_path,
+                        sink,
+                        &*emitted,
+                        context_lines,
                     );
 
-                    if search_result.is_ok() && !file_results.is_empty() {
-                        let mut results = results.lock().unwrap();
-                        results.extend(file_results);
+                    let search_result = match decompressor {
+                        Some((bin, args)) => {
+                            match Command::new(bin)
+                                .args(args)
+                                .arg(path)
+                                .stdout(Stdio::piped())
+                                .stderr(Stdio::null())
+                                .spawn()
+                            {
+                                Ok(mut child) => {
+                                    let result = match child.stdout.take() {
+                                        Some(stdout) => {
+                                            searcher.search_reader(&matcher, stdout, item_sink)
+                                        }
+                                        None => Ok(()),
+                                    };
+                                    let _ = child.wait();
+                                    result
+                                }
+                                Err(_) => searcher.search_path(&matcher, path, item_sink),
+                            }
+                        }
+                        None => searcher.search_path(&matcher, path, item_sink),
+                    };
+
+                    if search_result.is_err() {
+                        return ignore::WalkState::Continue;
                     }
 
                     ignore::WalkState::Continue
                 })
             });
 
-        let final_results: Vec<GrepItem> = {
-            let results_vec = match Arc::try_unwrap(results) {
-                Ok(mutex) => mutex.into_inner().unwrap(),
-                Err(arc) => arc.lock().unwrap().clone(),
-            };
-            results_vec.into_iter().take(*max_results).collect()
-        };
+        Ok(())
+    }
+
+    /// Perform grep search in the directory, collecting every match into a
+    /// `Vec`. Implemented by draining [`Self::grep_search_streaming`].
+    pub fn grep_search(
+        &self,
+        pattern: &str,
+        max_results: usize,
+        max_threads: usize,
+        context_lines: usize,
+        decompress: bool,
+    ) -> Result<Vec<GrepItem>, Error> {
+        let results = Mutex::new(Vec::new());
+        let cancel = Arc::new(AtomicBool::new(false));
+
+        self.grep_search_streaming(
+            pattern,
+            max_results,
+            max_threads,
+            context_lines,
+            decompress,
+            cancel,
+            |item| {
+                results.lock().unwrap().push(item);
+            },
+        )?;
+
+        let final_results: Vec<GrepItem> =
+            results.into_inner().unwrap().into_iter().take(max_results).collect();
 
         debug!("Grep search completed, found {} matches", final_results.len());
         Ok(final_results)
@@ -168,13 +398,21 @@ impl ContentSearcher {
         fuzzy_query: &str,
         max_results: usize,
         max_threads: usize,
+        context_lines: usize,
+        decompress: bool,
     ) -> Result<GrepSearchResult, Error> {
         // Convert the fuzzy query into a permissive regex pattern
         let fuzzy_regex = Self::fuzzy_query_to_regex(grep_pattern);
         info!("Fuzzy regex pattern: {}", fuzzy_regex);
 
         // First, do the grep search with the fuzzy regex
-        let grep_results = self.grep_search(&fuzzy_regex, max_results * 2, max_threads)?;
+        let grep_results = self.grep_search(
+            &fuzzy_regex,
+            max_results * 2,
+            max_threads,
+            context_lines,
+            decompress,
+        )?;
 
         if grep_results.is_empty() {
             return Ok(GrepSearchResult {
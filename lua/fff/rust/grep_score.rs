@@ -9,6 +9,117 @@ pub struct GrepScoringContext<'a> {
     pub max_threads: usize,
 }
 
+/// How a single query term is matched against a grep line, following fzf's
+/// extended-search grammar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TermMode {
+    /// Plain `foo` — permissive fuzzy match (via `neo_frizbee`).
+    Fuzzy,
+    /// `'foo` — exact substring match.
+    Exact,
+    /// `^foo` — anchored to the start of the line.
+    Prefix,
+    /// `foo$` — anchored to the end of the line.
+    Suffix,
+    /// `!foo` — reject any line containing `foo`.
+    Negation,
+}
+
+/// A single space-separated term of a structured grep query.
+#[derive(Debug, Clone)]
+struct QueryTerm {
+    text: String,
+    mode: TermMode,
+}
+
+/// Score awarded per matched character for exact/anchor terms, which bypass
+/// `neo_frizbee` and therefore have no intrinsic score of their own.
+const EXACT_TERM_CHAR_SCORE: i32 = 8;
+
+/// Distance-penalty factor for a gap of one character between matched characters.
+const BASE_DISTANCE_PENALTY: f32 = 0.6;
+/// Extra reduction applied per additional character of gap.
+const ADDITIONAL_DISTANCE_PENALTY: f32 = 0.05;
+/// Lower bound on the distance-penalty factor for a single gap.
+const MIN_DISTANCE_PENALTY: f32 = 0.2;
+
+/// Greedily locate `needle`'s characters as a subsequence of `haystack`,
+/// returning the index of each matched character. Returns `None` when not
+/// every character can be matched (e.g. the match lived in the path component
+/// rather than the line), in which case no distance penalty applies.
+fn matched_positions(needle: &str, haystack: &str) -> Option<Vec<usize>> {
+    let mut positions = Vec::with_capacity(needle.chars().count());
+    let mut hay = haystack.chars().enumerate();
+    for nc in needle.chars() {
+        let found = hay.by_ref().find(|&(_, hc)| hc == nc);
+        match found {
+            Some((idx, _)) => positions.push(idx),
+            None => return None,
+        }
+    }
+    Some(positions)
+}
+
+/// Compute the combined distance-penalty multiplier for a set of matched
+/// positions, following Zed's scheme: adjacent characters (gap of zero) incur
+/// no penalty, while each gap reduces the multiplier starting at
+/// [`BASE_DISTANCE_PENALTY`], dropping by [`ADDITIONAL_DISTANCE_PENALTY`] per
+/// extra character and floored at [`MIN_DISTANCE_PENALTY`].
+fn distance_multiplier(positions: &[usize]) -> f32 {
+    let mut multiplier = 1.0f32;
+    for pair in positions.windows(2) {
+        let gap = pair[1].saturating_sub(pair[0]).saturating_sub(1);
+        if gap == 0 {
+            continue;
+        }
+        let factor = (BASE_DISTANCE_PENALTY - ADDITIONAL_DISTANCE_PENALTY * (gap - 1) as f32)
+            .max(MIN_DISTANCE_PENALTY);
+        multiplier *= factor;
+    }
+    multiplier
+}
+
+/// Parse a fuzzy query into AND-ed [`QueryTerm`]s using fzf's operator grammar:
+/// plain `foo` is fuzzy, `'foo` is an exact substring, `^foo` anchors to the
+/// start, `foo$` anchors to the end, and `!foo` negates. Empty tokens (and
+/// bare operators with no payload) are dropped.
+fn parse_query(query: &str) -> Vec<QueryTerm> {
+    query
+        .split_whitespace()
+        .filter_map(|raw| {
+            if let Some(rest) = raw.strip_prefix('!') {
+                (!rest.is_empty()).then(|| QueryTerm {
+                    text: rest.to_lowercase(),
+                    mode: TermMode::Negation,
+                })
+            } else if let Some(rest) = raw.strip_prefix('\'') {
+                (!rest.is_empty()).then(|| QueryTerm {
+                    text: rest.to_lowercase(),
+                    mode: TermMode::Exact,
+                })
+            } else if let Some(rest) = raw.strip_prefix('^') {
+                (!rest.is_empty()).then(|| QueryTerm {
+                    text: rest.to_lowercase(),
+                    mode: TermMode::Prefix,
+                })
+            } else if let Some(rest) = raw.strip_suffix('$') {
+                (!rest.is_empty()).then(|| QueryTerm {
+                    text: rest.to_lowercase(),
+                    mode: TermMode::Suffix,
+                })
+            } else {
+                // Lowercase fuzzy terms too: the haystacks are lowercased, so an
+                // uppercase character here would never match. Smartcase bonuses
+                // still key off the original query via `has_uppercase_letter`.
+                Some(QueryTerm {
+                    text: raw.to_lowercase(),
+                    mode: TermMode::Fuzzy,
+                })
+            }
+        })
+        .collect()
+}
+
 /// Score grep results with fuzzy matching on path and line content
 pub fn match_and_score_grep_items(
     items: &[GrepItem],
@@ -42,34 +153,115 @@ pub fn match_and_score_grep_items(
         })
         .collect();
 
-    let matches = neo_frizbee::match_list_parallel(context.query, &haystack, &options, context.max_threads);
-    let total_matched = matches.len();
-
-    // Also match just the line content for bonus scoring
+    // Lowercase line content, used both for the line-match bonus and for the
+    // direct substring/anchor checks of exact/prefix/suffix/negation terms.
     let line_haystack: Vec<_> = items
         .iter()
         .map(|item| item.line_content.to_lowercase())
         .collect();
 
-    let line_matches = neo_frizbee::match_list_parallel(
-        context.query,
-        &line_haystack,
-        &options,
-        context.max_threads,
-    );
+    // Split the query into AND-ed terms. Positive terms intersect; negative
+    // terms filter. An index survives only if every positive term matches it
+    // and no negative term does, and its base score is the sum of the per-term
+    // scores.
+    let terms = parse_query(context.query);
+    let mut base_scores: std::collections::HashMap<usize, i32> = std::collections::HashMap::new();
+    let mut exact_map: std::collections::HashMap<usize, bool> = std::collections::HashMap::new();
+    let mut surviving: Option<std::collections::HashSet<usize>> = None;
+
+    let positives = || terms.iter().filter(|t| t.mode != TermMode::Negation);
+
+    if positives().next().is_none() {
+        // Nothing to rank on (e.g. a query made up entirely of negations):
+        // every item survives with a zero base score.
+        surviving = Some((0..items.len()).collect());
+    }
+
+    for term in positives() {
+        let mut term_hits: std::collections::HashSet<usize> = std::collections::HashSet::new();
+
+        match term.mode {
+            TermMode::Fuzzy => {
+                let matches = neo_frizbee::match_list_parallel(
+                    &term.text,
+                    &haystack,
+                    &options,
+                    context.max_threads,
+                );
+                for m in matches {
+                    let index = m.index as usize;
+                    term_hits.insert(index);
+                    *base_scores.entry(index).or_insert(0) += m.score as i32;
+                    let e = exact_map.entry(index).or_insert(true);
+                    *e = *e && m.exact;
+                }
+            }
+            TermMode::Exact | TermMode::Prefix | TermMode::Suffix => {
+                let term_score = term.text.chars().count() as i32 * EXACT_TERM_CHAR_SCORE;
+                for (index, line) in line_haystack.iter().enumerate() {
+                    let hit = match term.mode {
+                        TermMode::Exact => line.contains(&term.text),
+                        TermMode::Prefix => line.starts_with(&term.text),
+                        TermMode::Suffix => line.ends_with(&term.text),
+                        _ => unreachable!(),
+                    };
+                    if hit {
+                        term_hits.insert(index);
+                        *base_scores.entry(index).or_insert(0) += term_score;
+                        exact_map.entry(index).or_insert(true);
+                    }
+                }
+            }
+            TermMode::Negation => unreachable!(),
+        }
+
+        surviving = Some(match surviving.take() {
+            Some(prev) => prev.intersection(&term_hits).copied().collect(),
+            None => term_hits,
+        });
+    }
+
+    let mut surviving = surviving.unwrap_or_default();
 
-    // Create a map for quick line match lookup
+    // Drop any index matched by a negation term.
+    for term in terms.iter().filter(|t| t.mode == TermMode::Negation) {
+        surviving.retain(|&index| !line_haystack[index].contains(&term.text));
+    }
+
+    let total_matched = surviving.len();
+
+    // The text of every positive term, used for the line-match bonus. Built
+    // from the parsed terms so operator sigils never leak into matching.
+    let positive_terms: Vec<&str> = terms
+        .iter()
+        .filter(|t| t.mode != TermMode::Negation)
+        .map(|t| t.text.as_str())
+        .collect();
+
+    // Line-match bonus: reward lines whose own content matches the positive
+    // terms strongly, independent of the path component of the combined
+    // haystack. Joining the term texts keeps sigils and negations out of it.
+    let line_query = positive_terms.join(" ");
     let mut line_match_map = std::collections::HashMap::new();
-    for m in line_matches {
-        line_match_map.insert(m.index as usize, m.score);
+    if !line_query.is_empty() {
+        let line_matches = neo_frizbee::match_list_parallel(
+            &line_query,
+            &line_haystack,
+            &options,
+            context.max_threads,
+        );
+        for m in line_matches {
+            line_match_map.insert(m.index as usize, m.score);
+        }
     }
 
-    let mut results: Vec<_> = matches
+    let surviving: Vec<usize> = surviving.into_iter().collect();
+
+    let mut results: Vec<_> = surviving
         .into_par_iter()
-        .map(|m| {
-            let index = m.index as usize;
+        .map(|index| {
             let item = &items[index];
-            let base_score = m.score as i32;
+            let base_score = base_scores.get(&index).copied().unwrap_or(0);
 
             // Bonus if the line content itself is a strong match
             let line_match_bonus = line_match_map
@@ -97,10 +289,25 @@ pub fn match_and_score_grep_items(
             // Bonus for certain file types or important files
             let file_type_bonus = get_file_type_bonus(&item.relative_path);
 
+            // Penalize matches whose characters are smeared across the line so
+            // that tight, contiguous hits rank above scattered ones. The penalty
+            // is computed per fuzzy term against its own matched run — a
+            // single concatenation would be order-dependent across multi-term
+            // queries. Exact/anchor terms match contiguously, so they are
+            // skipped and never incur a penalty.
+            let mut multiplier = 1.0f32;
+            for term in terms.iter().filter(|t| t.mode == TermMode::Fuzzy) {
+                if let Some(positions) = matched_positions(&term.text, &line_haystack[index]) {
+                    multiplier *= distance_multiplier(&positions);
+                }
+            }
+            let distance_penalty = (base_score as f32 * (1.0 - multiplier)).round() as i32;
+
             let total = base_score
                 .saturating_add(line_match_bonus)
                 .saturating_add(position_bonus)
-                .saturating_add(file_type_bonus);
+                .saturating_add(file_type_bonus)
+                .saturating_sub(distance_penalty);
 
             let score = Score {
                 total,
@@ -108,9 +315,9 @@ pub fn match_and_score_grep_items(
                 filename_bonus: line_match_bonus,
                 special_filename_bonus: file_type_bonus,
                 frecency_boost: position_bonus,
-                distance_penalty: 0,
+                distance_penalty,
                 current_file_penalty: 0,
-                exact_match: m.exact,
+                exact_match: exact_map.get(&index).copied().unwrap_or(false),
                 match_type: "grep",
             };
 
@@ -162,3 +369,98 @@ fn get_file_type_bonus(path: &str) -> i32 {
         0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn grep_item(relative_path: &str, line: &str) -> GrepItem {
+        GrepItem {
+            path: PathBuf::from(relative_path),
+            relative_path: relative_path.to_string(),
+            line_number: 1,
+            line_content: line.to_string(),
+            column: 0,
+            match_ranges: Vec::new(),
+            context_before: Vec::new(),
+            context_after: Vec::new(),
+        }
+    }
+
+    fn modes(query: &str) -> Vec<(String, TermMode)> {
+        parse_query(query)
+            .into_iter()
+            .map(|t| (t.text, t.mode))
+            .collect()
+    }
+
+    #[test]
+    fn parses_operator_sigils() {
+        assert_eq!(
+            modes("'foo ^bar baz$ !qux plain"),
+            vec![
+                ("foo".to_string(), TermMode::Exact),
+                ("bar".to_string(), TermMode::Prefix),
+                ("baz".to_string(), TermMode::Suffix),
+                ("qux".to_string(), TermMode::Negation),
+                ("plain".to_string(), TermMode::Fuzzy),
+            ]
+        );
+    }
+
+    #[test]
+    fn lowercases_fuzzy_terms() {
+        assert_eq!(modes("Foo"), vec![("foo".to_string(), TermMode::Fuzzy)]);
+    }
+
+    #[test]
+    fn drops_bare_operators() {
+        assert!(parse_query("! ' ^").is_empty());
+    }
+
+    #[test]
+    fn negation_and_intersection_filter_lines() {
+        // Exact AND-term keeps lines containing "error"; negation drops any line
+        // containing "warn". Using only exact/negation terms keeps the outcome
+        // independent of the fuzzy matcher.
+        let items = vec![
+            grep_item("src/a.rs", "an error occurred"),
+            grep_item("src/b.rs", "error and warn together"),
+            grep_item("src/c.rs", "all good here"),
+        ];
+        let context = GrepScoringContext {
+            query: "'error !warn",
+            max_results: 10,
+            max_typos: 2,
+            max_threads: 1,
+        };
+
+        let (results, _scores, total_matched) = match_and_score_grep_items(&items, &context);
+
+        assert_eq!(total_matched, 1);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].relative_path, "src/a.rs");
+    }
+
+    #[test]
+    fn adjacent_positions_are_not_penalized() {
+        assert_eq!(distance_multiplier(&[0, 1, 2, 3]), 1.0);
+        assert_eq!(distance_multiplier(&[5]), 1.0);
+    }
+
+    #[test]
+    fn gaps_reduce_the_multiplier() {
+        // Gap of 1 -> BASE_DISTANCE_PENALTY.
+        assert!((distance_multiplier(&[0, 2]) - 0.6).abs() < 1e-6);
+        // Gap of 3 -> 0.6 - 0.05 * 2 = 0.5.
+        assert!((distance_multiplier(&[0, 4]) - 0.5).abs() < 1e-6);
+        // Two independent one-character gaps compound: 0.6 * 0.6.
+        assert!((distance_multiplier(&[0, 2, 4]) - 0.36).abs() < 1e-6);
+    }
+
+    #[test]
+    fn large_gaps_are_floored() {
+        assert!((distance_multiplier(&[0, 50]) - MIN_DISTANCE_PENALTY).abs() < 1e-6);
+    }
+}